@@ -1,8 +1,10 @@
+use crate::shell::Shell;
 use clap::Parser;
 use notify_rust::{Timeout, Urgency};
 use serde_derive::Deserialize;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use std::vec::Vec;
 
 #[derive(Debug, Deserialize)]
@@ -14,13 +16,29 @@ struct TomlProfile {
     title: Option<String>,
     urgency: Option<String>,
     command: Option<String>,
+    shell: Option<String>,
+    capture: Option<bool>,
+    capture_lines: Option<usize>,
+    fail_title: Option<String>,
+    fail_icon: Option<String>,
+    fail_urgency: Option<String>,
+    run_timeout: Option<String>,
+    no_process_group: Option<bool>,
+    inherits: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct TomlConfig {
+    version: Option<u32>,
     profile: Option<Vec<TomlProfile>>,
 }
 
+/// Current on-disk config schema version. Bump this and teach
+/// `check_config_version` about the migration whenever a breaking change
+/// is made to `TomlConfig`/`TomlProfile`, so old config files are upgraded
+/// instead of silently mis-parsed.
+const CONFIG_VERSION: u32 = 1;
+
 pub struct Config {
     pub icon: String,
     pub message: String,
@@ -28,17 +46,39 @@ pub struct Config {
     pub title: String,
     pub urgency: Urgency,
     pub command: Vec<String>,
+    pub shell: Shell,
+    pub capture: bool,
+    pub capture_lines: usize,
+    pub fail_title: String,
+    pub fail_icon: String,
+    pub fail_urgency: Urgency,
+    pub run_timeout: Option<Duration>,
+    pub no_process_group: bool,
 }
 
 impl Config {
     fn default_config() -> Config {
+        let title = Config::default_title();
+        let icon = Config::default_icon();
+        let urgency = Config::default_urgency();
+
         Config {
-            icon: Config::default_icon(),
+            icon: icon.clone(),
             message: Config::default_message(),
             timeout: Config::default_timeout(),
-            title: Config::default_title(),
-            urgency: Config::default_urgency(),
+            title: title.clone(),
+            urgency,
             command: Config::default_command(),
+            shell: Config::default_shell(),
+            capture: Config::default_capture(),
+            capture_lines: Config::default_capture_lines(),
+            // unless a profile says otherwise, a failed run reuses the
+            // success title/icon/urgency
+            fail_title: title,
+            fail_icon: icon,
+            fail_urgency: urgency,
+            run_timeout: Config::default_run_timeout(),
+            no_process_group: Config::default_no_process_group(),
         }
     }
 
@@ -47,7 +87,7 @@ impl Config {
     }
 
     fn default_message() -> String {
-        String::new()
+        String::from("{status} in {duration}")
     }
 
     fn default_title() -> String {
@@ -66,16 +106,45 @@ impl Config {
         vec![]
     }
 
-    pub fn new() -> Config {
-        let args = std::env::args().collect();
-        let config_path = get_config_path();
+    fn default_shell() -> Shell {
+        Shell::default_shell()
+    }
+
+    fn default_capture() -> bool {
+        false
+    }
+
+    fn default_capture_lines() -> usize {
+        5
+    }
+
+    fn default_run_timeout() -> Option<Duration> {
+        None
+    }
+
+    fn default_no_process_group() -> bool {
+        false
+    }
+
+    /// Reads CLI args and the config file once for this invocation.
+    ///
+    /// Note on scope: live reload (re-reading the config file while a run
+    /// is in progress) is intentionally not implemented. notify-complete
+    /// spawns one child and exits; there's no long-running process for a
+    /// reloaded config to apply to, so `--config`/versioning cover the
+    /// rest of this request but reloading is out of scope until the tool
+    /// itself grows a persistent/daemon mode.
+    pub fn new() -> Result<Config, String> {
+        let arguments: Vec<String> = std::env::args().collect();
+        let config_override = args::Args::parse_from(arguments.clone()).config;
+        let config_path = get_config_path(config_override.as_deref());
         let toml_config = read_config_file(config_path.as_path());
 
-        return Config::new_from(args, &toml_config);
+        return Config::new_from(arguments, &toml_config);
     }
 
     //fn new_from(arguments: &mut dyn std::iter::Iterator<Item = String>, toml_config: &Option<TomlConfig>) -> Config {
-    fn new_from(arguments: Vec<String>, toml_config: &Option<TomlConfig>) -> Config {
+    fn new_from(arguments: Vec<String>, toml_config: &Option<TomlConfig>) -> Result<Config, String> {
         let args = args::Args::parse_from(arguments);
 
         let mut conf = match toml_config {
@@ -83,7 +152,7 @@ impl Config {
                 if tc.profile.is_none() {
                     Config::default_config()
                 } else {
-                    Config::from_toml(args.get_profile(), tc)
+                    Config::from_toml(args.get_profile(), tc)?
                 }
             }
             None => Config::default_config(),
@@ -105,8 +174,40 @@ impl Config {
             conf.urgency = Config::parse_urgency(args.urgency.as_ref().unwrap().as_str());
         }
 
+        if args.shell.is_some() {
+            conf.shell = Shell::parse(args.shell.as_ref().unwrap().as_str());
+        }
+
+        if args.capture {
+            conf.capture = true;
+        }
+
+        if args.tail_lines.is_some() {
+            conf.capture_lines = args.tail_lines.unwrap();
+        }
+
+        if args.fail_title.is_some() {
+            conf.fail_title = String::from(args.fail_title.as_ref().unwrap());
+        }
+
+        if args.fail_icon.is_some() {
+            conf.fail_icon = String::from(args.fail_icon.as_ref().unwrap());
+        }
+
+        if args.fail_urgency.is_some() {
+            conf.fail_urgency = Config::parse_urgency(args.fail_urgency.as_ref().unwrap().as_str());
+        }
+
+        if args.run_timeout.is_some() {
+            conf.run_timeout = Config::parse_run_timeout(args.run_timeout.as_ref().unwrap().as_str());
+        }
+
+        if args.no_process_group {
+            conf.no_process_group = true;
+        }
+
         conf.command = args.command;
-        conf
+        Ok(conf)
     }
 
     pub fn parse_timeout(timeout: &str) -> Timeout {
@@ -136,27 +237,133 @@ impl Config {
     }
 
     fn parse_command(command: &str) -> Vec<String> {
-        let components = command.split_whitespace();
-        let mut cmd_vec = Vec::new();
-        for component in components {
-            cmd_vec.push(String::from(component));
+        split_shell_words(command)
+    }
+
+    pub fn parse_run_timeout(run_timeout: &str) -> Option<Duration> {
+        match humantime::parse_duration(run_timeout) {
+            Ok(d) => Some(d),
+            Err(_) => {
+                eprintln!(
+                    "notify-complete: Error parsing run_timeout value '{}'",
+                    run_timeout
+                );
+                None
+            }
         }
-        cmd_vec
     }
 
-    fn from_toml(profile: &str, toml: &TomlConfig) -> Config {
+    fn from_toml(profile: &str, toml: &TomlConfig) -> Result<Config, String> {
         if toml.profile.is_none() {
             // no profiles in config file, return default config
-            return Config::default_config();
+            return Ok(Config::default_config());
         }
 
-        for toml_profile in toml.profile.as_ref().unwrap() {
+        let profiles = toml.profile.as_ref().unwrap();
+
+        for toml_profile in profiles {
             if profile == toml_profile.name {
-                return Config::from_toml_profile(toml_profile);
+                return match Config::resolve_profile_chain(toml_profile, profiles) {
+                    Ok(merged) => Ok(Config::from_toml_profile(&merged)),
+                    Err(e) => {
+                        eprintln!("notify-complete: {}", e);
+                        Ok(Config::default_config())
+                    }
+                };
+            }
+        }
+
+        let available: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+        Err(format!(
+            "Profile '{}' not found in config file; available profiles: {}",
+            profile,
+            available.join(", ")
+        ))
+    }
+
+    /// Walks `start`'s `inherits` chain, merging each ancestor's fields in so
+    /// that only fields left unset along the entire chain fall back to
+    /// `Config::default_*`. A profile's own fields always win over its
+    /// ancestors'. Returns an error if the chain references an unknown
+    /// profile or loops back on itself.
+    fn resolve_profile_chain(
+        start: &TomlProfile,
+        profiles: &[TomlProfile],
+    ) -> Result<TomlProfile, String> {
+        let mut chain: Vec<&TomlProfile> = vec![start];
+        let mut visited = vec![start.name.clone()];
+        let mut current = start;
+
+        while let Some(parent_name) = &current.inherits {
+            if visited.contains(parent_name) {
+                visited.push(parent_name.clone());
+                return Err(format!(
+                    "Profile inheritance cycle detected: {}",
+                    visited.join(" -> ")
+                ));
             }
+
+            let parent = profiles.iter().find(|p| &p.name == parent_name).ok_or_else(|| {
+                format!(
+                    "Profile '{}' inherits from unknown profile '{}'",
+                    current.name, parent_name
+                )
+            })?;
+
+            visited.push(parent_name.clone());
+            chain.push(parent);
+            current = parent;
+        }
+
+        Ok(Config::merge_profile_chain(&chain))
+    }
+
+    /// Merges a profile chain (most specific first) into a single
+    /// `TomlProfile`, keeping the first `Some` value seen for each field.
+    fn merge_profile_chain(chain: &[&TomlProfile]) -> TomlProfile {
+        let mut merged = TomlProfile {
+            name: chain[0].name.clone(),
+            icon: None,
+            message: None,
+            timeout: None,
+            title: None,
+            urgency: None,
+            command: None,
+            shell: None,
+            capture: None,
+            capture_lines: None,
+            fail_title: None,
+            fail_icon: None,
+            fail_urgency: None,
+            run_timeout: None,
+            no_process_group: None,
+            inherits: None,
+        };
+
+        for profile in chain {
+            merged.icon = merged.icon.take().or_else(|| profile.icon.clone());
+            merged.message = merged.message.take().or_else(|| profile.message.clone());
+            merged.timeout = merged.timeout.take().or_else(|| profile.timeout.clone());
+            merged.title = merged.title.take().or_else(|| profile.title.clone());
+            merged.urgency = merged.urgency.take().or_else(|| profile.urgency.clone());
+            merged.command = merged.command.take().or_else(|| profile.command.clone());
+            merged.shell = merged.shell.take().or_else(|| profile.shell.clone());
+            merged.capture = merged.capture.or(profile.capture);
+            merged.capture_lines = merged.capture_lines.or(profile.capture_lines);
+            merged.fail_title = merged.fail_title.take().or_else(|| profile.fail_title.clone());
+            merged.fail_icon = merged.fail_icon.take().or_else(|| profile.fail_icon.clone());
+            merged.fail_urgency = merged
+                .fail_urgency
+                .take()
+                .or_else(|| profile.fail_urgency.clone());
+            merged.run_timeout = merged
+                .run_timeout
+                .take()
+                .or_else(|| profile.run_timeout.clone());
+            merged.no_process_group = merged.no_process_group.or(profile.no_process_group);
         }
 
-        return Config::default_config();
+        merged
     }
 
     fn from_toml_profile(profile: &TomlProfile) -> Config {
@@ -190,6 +397,48 @@ impl Config {
             None => Config::default_command(),
         };
 
+        let shell = match profile.shell.as_ref() {
+            Some(s) => Shell::parse(s.as_str()),
+            None => Config::default_shell(),
+        };
+
+        let capture = match profile.capture {
+            Some(c) => c,
+            None => Config::default_capture(),
+        };
+
+        let capture_lines = match profile.capture_lines {
+            Some(n) => n,
+            None => Config::default_capture_lines(),
+        };
+
+        // unless a profile says otherwise, a failed run reuses this
+        // profile's success title/icon/urgency
+        let fail_title = match &profile.fail_title {
+            Some(t) => String::from(t),
+            None => title.clone(),
+        };
+
+        let fail_icon = match &profile.fail_icon {
+            Some(i) => String::from(i),
+            None => icon.clone(),
+        };
+
+        let fail_urgency = match profile.fail_urgency.as_ref() {
+            Some(u) => Config::parse_urgency(u.as_str()),
+            None => urgency,
+        };
+
+        let run_timeout = match profile.run_timeout.as_ref() {
+            Some(t) => Config::parse_run_timeout(t.as_str()),
+            None => Config::default_run_timeout(),
+        };
+
+        let no_process_group = match profile.no_process_group {
+            Some(n) => n,
+            None => Config::default_no_process_group(),
+        };
+
         Config {
             icon,
             message,
@@ -197,11 +446,83 @@ impl Config {
             title,
             urgency,
             command,
+            shell,
+            capture,
+            capture_lines,
+            fail_title,
+            fail_icon,
+            fail_urgency,
+            run_timeout,
+            no_process_group,
         }
     }
 }
 
-fn get_config_path() -> PathBuf {
+/// Splits a TOML `command` string into argv-style words, honoring single
+/// and double quotes and backslash escapes so quoted tokens survive (e.g.
+/// `echo "hello world"` becomes `["echo", "hello world"]` instead of three
+/// whitespace-split pieces).
+fn split_shell_words(input: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' if in_word => {
+                words.push(std::mem::take(&mut current));
+                in_word = false;
+            }
+            ' ' | '\t' => continue,
+            '\'' => {
+                in_word = true;
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                    current.push(c);
+                }
+            }
+            '"' => {
+                in_word = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' if matches!(chars.peek(), Some('"') | Some('\\')) => {
+                            current.push(chars.next().unwrap());
+                        }
+                        other => current.push(other),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            other => {
+                in_word = true;
+                current.push(other);
+            }
+        }
+    }
+
+    if in_word {
+        words.push(current);
+    }
+
+    words
+}
+
+/// Returns the config file to read: `override_path` if the caller passed
+/// `--config <path>`, otherwise the platform default config location.
+fn get_config_path(override_path: Option<&str>) -> PathBuf {
+    if let Some(path) = override_path {
+        return PathBuf::from(path);
+    }
+
     const CONFIG_DIR: &str = "notify-complete";
     const CONFIG_FILE: &str = "config.toml";
 
@@ -248,18 +569,54 @@ fn read_config_file(path: &Path) -> Option<TomlConfig> {
         }
     };
 
+    check_config_version(conf.version);
+
     Some(conf)
 }
 
+/// Warns when `version` is missing or older than `CONFIG_VERSION`. There is
+/// no schema history to migrate yet, but this is the seat for that logic
+/// once a future change needs one, rather than letting an old config file
+/// be silently mis-parsed under a new schema.
+fn check_config_version(version: Option<u32>) {
+    match version {
+        Some(v) if v >= CONFIG_VERSION => {}
+        Some(v) => eprintln!(
+            "notify-complete: config file version {} is older than {} -- using it as-is",
+            v, CONFIG_VERSION
+        ),
+        None => eprintln!(
+            "notify-complete: config file has no 'version' field -- assuming version {}",
+            CONFIG_VERSION
+        ),
+    }
+}
+
+#[cfg(test)]
+mod config_path_tests {
+    use super::get_config_path;
+    use std::path::PathBuf;
+
+    #[test]
+    fn override_path_is_used_verbatim() {
+        let path = get_config_path(Some("/tmp/notify-complete.toml"));
+        assert_eq!(path, PathBuf::from("/tmp/notify-complete.toml"));
+    }
+}
+
 #[cfg(test)]
 mod toml_tests {
     use super::{Config, TomlConfig, TomlProfile};
+    use crate::shell::Shell;
     use notify_rust::{Timeout, Urgency};
 
     #[test]
     fn config_defaults() {
-        let tc = TomlConfig { profile: None };
-        let c = Config::from_toml("doesn't matter", &tc);
+        let tc = TomlConfig {
+            version: None,
+            profile: None,
+        };
+        let c = Config::from_toml("doesn't matter", &tc).unwrap();
 
         assert_eq!(c.icon, Config::default_icon());
         assert_eq!(c.message, Config::default_message());
@@ -267,6 +624,7 @@ mod toml_tests {
         assert_eq!(c.title, Config::default_title());
         assert_eq!(c.urgency, Config::default_urgency());
         assert_eq!(c.command, Config::default_command());
+        assert_eq!(c.shell, Config::default_shell());
     }
 
     #[test]
@@ -279,13 +637,23 @@ mod toml_tests {
             title: None,
             urgency: None,
             command: None,
+            shell: None,
+            capture: None,
+            capture_lines: None,
+            fail_title: None,
+            fail_icon: None,
+            fail_urgency: None,
+            run_timeout: None,
+            no_process_group: None,
+            inherits: None,
         };
 
         let tc = TomlConfig {
+            version: None,
             profile: Some(vec![tp]),
         };
 
-        let c = Config::from_toml("test", &tc);
+        let c = Config::from_toml("test", &tc).unwrap();
 
         assert_eq!(c.icon, Config::default_icon());
         assert_eq!(c.message, Config::default_message());
@@ -293,6 +661,14 @@ mod toml_tests {
         assert_eq!(c.title, Config::default_title());
         assert_eq!(c.urgency, Config::default_urgency());
         assert_eq!(c.command, Config::default_command());
+        assert_eq!(c.shell, Config::default_shell());
+        assert_eq!(c.capture, Config::default_capture());
+        assert_eq!(c.capture_lines, Config::default_capture_lines());
+        assert_eq!(c.fail_title, Config::default_title());
+        assert_eq!(c.fail_icon, Config::default_icon());
+        assert_eq!(c.fail_urgency, Config::default_urgency());
+        assert_eq!(c.run_timeout, Config::default_run_timeout());
+        assert_eq!(c.no_process_group, Config::default_no_process_group());
     }
 
     #[test]
@@ -305,20 +681,26 @@ mod toml_tests {
             title: None,
             urgency: None,
             command: None,
+            shell: None,
+            capture: None,
+            capture_lines: None,
+            fail_title: None,
+            fail_icon: None,
+            fail_urgency: None,
+            run_timeout: None,
+            no_process_group: None,
+            inherits: None,
         };
 
         let tc = TomlConfig {
+            version: None,
             profile: Some(vec![tp]),
         };
 
-        let c = Config::from_toml("does not exist", &tc);
+        let err = Config::from_toml("does not exist", &tc).unwrap_err();
 
-        assert_eq!(c.icon, Config::default_icon());
-        assert_eq!(c.message, Config::default_message());
-        assert_eq!(c.timeout, Config::default_timeout());
-        assert_eq!(c.title, Config::default_title());
-        assert_eq!(c.urgency, Config::default_urgency());
-        assert_eq!(c.command, Config::default_command());
+        assert!(err.contains("does not exist"));
+        assert!(err.contains("test"));
     }
 
     #[test]
@@ -331,13 +713,23 @@ mod toml_tests {
             title: Some("title".to_string()),
             urgency: Some("critical".to_string()),
             command: Some("echo hello".to_string()),
+            shell: Some("sh".to_string()),
+            capture: Some(true),
+            capture_lines: Some(10),
+            fail_title: Some("fail title".to_string()),
+            fail_icon: Some("fail icon".to_string()),
+            fail_urgency: Some("low".to_string()),
+            run_timeout: Some("30s".to_string()),
+            no_process_group: Some(true),
+            inherits: None,
         };
 
         let tc = TomlConfig {
+            version: None,
             profile: Some(vec![tp]),
         };
 
-        let c = Config::from_toml("test", &tc);
+        let c = Config::from_toml("test", &tc).unwrap();
 
         assert_eq!(c.icon, "icon");
         assert_eq!(c.message, "message");
@@ -345,6 +737,151 @@ mod toml_tests {
         assert_eq!(c.title, "title");
         assert_eq!(c.urgency, Urgency::Critical);
         assert_eq!(c.command, vec!["echo", "hello"]);
+        assert_eq!(c.shell, Shell::Sh);
+        assert_eq!(c.capture, true);
+        assert_eq!(c.capture_lines, 10);
+        assert_eq!(c.fail_title, "fail title");
+        assert_eq!(c.fail_icon, "fail icon");
+        assert_eq!(c.fail_urgency, Urgency::Low);
+        assert_eq!(c.run_timeout, Some(std::time::Duration::from_secs(30)));
+        assert_eq!(c.no_process_group, true);
+    }
+
+    #[test]
+    fn profile_fail_fields_default_to_success_fields() {
+        let tp = TomlProfile {
+            name: "test".to_string(),
+            icon: Some("icon".to_string()),
+            message: None,
+            timeout: None,
+            title: Some("title".to_string()),
+            urgency: Some("critical".to_string()),
+            command: None,
+            shell: None,
+            capture: None,
+            capture_lines: None,
+            fail_title: None,
+            fail_icon: None,
+            fail_urgency: None,
+            run_timeout: None,
+            no_process_group: None,
+            inherits: None,
+        };
+
+        let tc = TomlConfig {
+            version: None,
+            profile: Some(vec![tp]),
+        };
+
+        let c = Config::from_toml("test", &tc).unwrap();
+
+        assert_eq!(c.fail_title, c.title);
+        assert_eq!(c.fail_icon, c.icon);
+        assert_eq!(c.fail_urgency, c.urgency);
+    }
+
+    #[test]
+    fn profile_inherits_merges_unset_fields_from_parent() {
+        let base = TomlProfile {
+            name: "base".to_string(),
+            icon: Some("base icon".to_string()),
+            message: None,
+            timeout: None,
+            title: Some("base title".to_string()),
+            urgency: Some("critical".to_string()),
+            command: None,
+            shell: None,
+            capture: None,
+            capture_lines: None,
+            fail_title: None,
+            fail_icon: None,
+            fail_urgency: None,
+            run_timeout: None,
+            no_process_group: None,
+            inherits: None,
+        };
+
+        let child = TomlProfile {
+            name: "child".to_string(),
+            icon: None,
+            message: None,
+            timeout: None,
+            title: Some("child title".to_string()),
+            urgency: None,
+            command: None,
+            shell: None,
+            capture: None,
+            capture_lines: None,
+            fail_title: None,
+            fail_icon: None,
+            fail_urgency: None,
+            run_timeout: None,
+            no_process_group: None,
+            inherits: Some("base".to_string()),
+        };
+
+        let tc = TomlConfig {
+            version: None,
+            profile: Some(vec![base, child]),
+        };
+
+        let c = Config::from_toml("child", &tc).unwrap();
+
+        // the child's own title wins over the parent's...
+        assert_eq!(c.title, "child title");
+        // ...but an unset field falls back through the inheritance chain
+        assert_eq!(c.icon, "base icon");
+        assert_eq!(c.urgency, Urgency::Critical);
+    }
+
+    #[test]
+    fn profile_inherits_cycle_falls_back_to_default() {
+        let a = TomlProfile {
+            name: "a".to_string(),
+            icon: None,
+            message: None,
+            timeout: None,
+            title: None,
+            urgency: None,
+            command: None,
+            shell: None,
+            capture: None,
+            capture_lines: None,
+            fail_title: None,
+            fail_icon: None,
+            fail_urgency: None,
+            run_timeout: None,
+            no_process_group: None,
+            inherits: Some("b".to_string()),
+        };
+
+        let b = TomlProfile {
+            name: "b".to_string(),
+            icon: None,
+            message: None,
+            timeout: None,
+            title: None,
+            urgency: None,
+            command: None,
+            shell: None,
+            capture: None,
+            capture_lines: None,
+            fail_title: None,
+            fail_icon: None,
+            fail_urgency: None,
+            run_timeout: None,
+            no_process_group: None,
+            inherits: Some("a".to_string()),
+        };
+
+        let tc = TomlConfig {
+            version: None,
+            profile: Some(vec![a, b]),
+        };
+
+        let c = Config::from_toml("a", &tc).unwrap();
+
+        assert_eq!(c.title, Config::default_title());
     }
 }
 
@@ -355,14 +892,18 @@ mod new_from_tests {
 
     #[test]
     fn default_values() {
-        let tc = TomlConfig { profile: None };
+        let tc = TomlConfig {
+            version: None,
+            profile: None,
+        };
         let c = Config::new_from(
             vec!["notify-complete", "sleep", "1"]
                 .iter()
                 .map(|s| s.to_string())
                 .collect(),
             &Some(tc),
-        );
+        )
+        .unwrap();
 
         assert_eq!(c.icon, Config::default_icon());
         assert_eq!(c.message, Config::default_message());
@@ -370,6 +911,11 @@ mod new_from_tests {
         assert_eq!(c.title, Config::default_title());
         assert_eq!(c.urgency, Config::default_urgency());
         assert_eq!(c.command, vec!["sleep", "1"]);
+        assert_eq!(c.shell, Config::default_shell());
+        assert_eq!(c.capture, Config::default_capture());
+        assert_eq!(c.capture_lines, Config::default_capture_lines());
+        assert_eq!(c.run_timeout, Config::default_run_timeout());
+        assert_eq!(c.no_process_group, Config::default_no_process_group());
     }
 
     #[test]
@@ -382,9 +928,19 @@ mod new_from_tests {
             title: Some("title".to_string()),
             urgency: Some("critical".to_string()),
             command: None,
+            shell: None,
+            capture: None,
+            capture_lines: None,
+            fail_title: None,
+            fail_icon: None,
+            fail_urgency: None,
+            run_timeout: None,
+            no_process_group: None,
+            inherits: None,
         };
 
         let tc = TomlConfig {
+            version: None,
             profile: Some(vec![tp]),
         };
 
@@ -394,7 +950,8 @@ mod new_from_tests {
                 .map(|s| s.to_string())
                 .collect(),
             &Some(tc),
-        );
+        )
+        .unwrap();
 
         assert_eq!(c.icon, "icon");
         assert_eq!(c.message, "message");
@@ -402,6 +959,45 @@ mod new_from_tests {
         assert_eq!(c.title, "title");
         assert_eq!(c.urgency, Urgency::Critical);
         assert_eq!(c.command, vec!["echo", "test"]);
+        assert_eq!(c.shell, Config::default_shell());
+    }
+
+    #[test]
+    fn unknown_profile_is_a_hard_error() {
+        let tp = TomlProfile {
+            name: "test".to_string(),
+            icon: None,
+            message: None,
+            timeout: None,
+            title: None,
+            urgency: None,
+            command: None,
+            shell: None,
+            capture: None,
+            capture_lines: None,
+            fail_title: None,
+            fail_icon: None,
+            fail_urgency: None,
+            run_timeout: None,
+            no_process_group: None,
+            inherits: None,
+        };
+
+        let tc = TomlConfig {
+            version: None,
+            profile: Some(vec![tp]),
+        };
+
+        let err = Config::new_from(
+            vec!["notify-complete", "-p", "does not exist", "echo", "test"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            &Some(tc),
+        )
+        .unwrap_err();
+
+        assert!(err.contains("does not exist"));
     }
 }
 
@@ -453,6 +1049,24 @@ mod value_parsing_tests {
         assert_eq!(urgency, Urgency::Normal);
     }
 
+    #[test]
+    fn run_timeout_value_seconds() {
+        let run_timeout = Config::parse_run_timeout("30s");
+        assert_eq!(run_timeout, Some(std::time::Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn run_timeout_value_minutes() {
+        let run_timeout = Config::parse_run_timeout("5m");
+        assert_eq!(run_timeout, Some(std::time::Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn run_timeout_value_invalid() {
+        let run_timeout = Config::parse_run_timeout("not a duration");
+        assert_eq!(run_timeout, None);
+    }
+
     #[test]
     fn urgency_value_critical() {
         let urgency = Config::parse_urgency("critical");
@@ -475,6 +1089,9 @@ mod args {
         )]
         profile: String,
 
+        #[clap(long, help = "Path to an alternate config file to use instead of the default location.")]
+        pub config: Option<String>,
+
         #[clap(short, long, help = "Title of the notification.")]
         pub title: Option<String>,
 
@@ -491,6 +1108,48 @@ mod args {
         #[clap(short, long, help = "Notification urgency (low, normal, critical)")]
         pub urgency: Option<String>,
 
+        #[clap(
+            long,
+            help = "Shell to run the command in: 'none', 'sh', 'cmd', or a custom program."
+        )]
+        pub shell: Option<String>,
+
+        #[clap(
+            long,
+            help = "Capture the command's stdout/stderr (still echoed to the terminal); the stderr tail is included in the notification on failure."
+        )]
+        pub capture: bool,
+
+        #[clap(
+            long,
+            help = "Number of trailing stderr lines to include in the notification on failure."
+        )]
+        pub tail_lines: Option<usize>,
+
+        #[clap(long, help = "Notification title to use when the command fails.")]
+        pub fail_title: Option<String>,
+
+        #[clap(long, help = "Notification icon to use when the command fails.")]
+        pub fail_icon: Option<String>,
+
+        #[clap(
+            long,
+            help = "Notification urgency to use when the command fails (low, normal, critical)."
+        )]
+        pub fail_urgency: Option<String>,
+
+        #[clap(
+            long,
+            help = "Maximum time the command may run (e.g. '30s', '5m') before it is killed."
+        )]
+        pub run_timeout: Option<String>,
+
+        #[clap(
+            long,
+            help = "Don't run the command in its own process group; signals reach it the same way they reach notify-complete."
+        )]
+        pub no_process_group: bool,
+
         #[clap(required = true, multiple_values = true, value_hint = ValueHint::CommandWithArguments, name = "cmd-with-args")]
         pub command: Vec<String>,
     }
@@ -520,10 +1179,19 @@ mod args {
             let parsed = Args::parse_from(args);
 
             assert_eq!(parsed.profile, "default");
+            assert_eq!(parsed.config, None);
             assert_eq!(parsed.title, None);
             assert_eq!(parsed.message, None);
             assert_eq!(parsed.timeout, None);
             assert_eq!(parsed.urgency, None);
+            assert_eq!(parsed.shell, None);
+            assert_eq!(parsed.capture, false);
+            assert_eq!(parsed.tail_lines, None);
+            assert_eq!(parsed.fail_title, None);
+            assert_eq!(parsed.fail_icon, None);
+            assert_eq!(parsed.fail_urgency, None);
+            assert_eq!(parsed.run_timeout, None);
+            assert_eq!(parsed.no_process_group, false);
             assert_eq!(parsed.command, vec!["fake-cmd"]);
         }
 
@@ -533,6 +1201,8 @@ mod args {
                 "notify-complete",
                 "-p",
                 "test-profile",
+                "--config",
+                "/tmp/notify-complete.toml",
                 "-t",
                 "Unit test",
                 "-m",
@@ -541,6 +1211,20 @@ mod args {
                 "never",
                 "-u",
                 "low",
+                "--shell",
+                "sh",
+                "--capture",
+                "--tail-lines",
+                "10",
+                "--fail-title",
+                "Fail title",
+                "--fail-icon",
+                "fail-icon",
+                "--fail-urgency",
+                "critical",
+                "--run-timeout",
+                "30s",
+                "--no-process-group",
                 "fake-cmd",
                 "--option",
                 "yes",
@@ -548,10 +1232,19 @@ mod args {
             let parsed = Args::parse_from(args);
 
             assert_eq!(parsed.profile, "test-profile");
+            assert_eq!(parsed.config.unwrap(), "/tmp/notify-complete.toml");
             assert_eq!(parsed.title.unwrap(), "Unit test");
             assert_eq!(parsed.message.unwrap(), "This is a unit test.");
             assert_eq!(parsed.timeout.unwrap(), "never");
             assert_eq!(parsed.urgency.unwrap(), "low");
+            assert_eq!(parsed.shell.unwrap(), "sh");
+            assert_eq!(parsed.capture, true);
+            assert_eq!(parsed.tail_lines.unwrap(), 10);
+            assert_eq!(parsed.fail_title.unwrap(), "Fail title");
+            assert_eq!(parsed.fail_icon.unwrap(), "fail-icon");
+            assert_eq!(parsed.fail_urgency.unwrap(), "critical");
+            assert_eq!(parsed.run_timeout.unwrap(), "30s");
+            assert_eq!(parsed.no_process_group, true);
             assert_eq!(parsed.command, vec!["fake-cmd", "--option", "yes"]);
         }
     }
@@ -1,26 +1,198 @@
 mod config;
+mod shell;
 
 use crate::config::Config;
 use humantime::format_duration;
 use notify_rust::Notification;
-use std::process::{self, Command};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{self, Child, Stdio};
+use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
-fn send_notification(conf: &config::Config, _exit_code: i32, duration: Duration) {
+/// Exit code reported when the child is killed for exceeding `run_timeout`,
+/// matching the convention used by the coreutils `timeout` command.
+const TIMED_OUT_EXIT_CODE: i32 = 124;
+
+/// Grace period between SIGTERM and SIGKILL when a child outlives its
+/// `run_timeout` on Unix.
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+/// How the wrapped command finished, for exit-code and notification purposes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChildOutcome {
+    Completed,
+    TimedOut,
+    Signaled(i32),
+}
+
+/// Substitutes `{exit_code}`, `{duration}`, `{command}`, `{status}`, and
+/// `{output_tail}` in `template`. `{{`/`}}` escape literal braces; any other
+/// `{token}` is left verbatim since it isn't one we know about.
+fn render_template(
+    template: &str,
+    exit_code: i32,
+    duration_str: &str,
+    command: &str,
+    status: &str,
+    output_tail: &str,
+) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                result.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                result.push('}');
+            }
+            '{' => {
+                let mut token = String::new();
+                let mut terminated = false;
+
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        terminated = true;
+                        break;
+                    }
+                    token.push(c);
+                }
+
+                if !terminated {
+                    result.push('{');
+                    result.push_str(&token);
+                    continue;
+                }
+
+                match token.as_str() {
+                    "exit_code" => result.push_str(&exit_code.to_string()),
+                    "duration" => result.push_str(duration_str),
+                    "command" => result.push_str(command),
+                    "status" => result.push_str(status),
+                    "output_tail" => result.push_str(output_tail),
+                    _ => {
+                        result.push('{');
+                        result.push_str(&token);
+                        result.push('}');
+                    }
+                }
+            }
+            other => result.push(other),
+        }
+    }
+
+    result
+}
+
+/// Renders `message_template` (every outcome -- completed, timed out, or
+/// killed by signal -- shares the same user-configurable template, so a
+/// custom `{status}`-aware message stays consistent no matter how the
+/// command finished) and, for backwards compatibility, appends the stderr
+/// tail on failure -- unless the template already pulled it in via
+/// `{output_tail}`, which would otherwise show up twice.
+#[allow(clippy::too_many_arguments)]
+fn build_message(
+    message_template: &str,
+    exit_code: i32,
+    duration_str: &str,
+    command_str: &str,
+    status_str: &str,
+    output_tail_str: &str,
+    success: bool,
+    stderr_tail: &[String],
+) -> String {
+    let mut message = render_template(
+        message_template,
+        exit_code,
+        duration_str,
+        command_str,
+        status_str,
+        output_tail_str,
+    );
+
+    if !success && !stderr_tail.is_empty() && !message_template.contains("{output_tail}") {
+        message.push_str("\n\n");
+        message.push_str(output_tail_str);
+    }
+
+    message
+}
+
+fn send_notification(
+    conf: &Config,
+    exit_code: i32,
+    duration: Duration,
+    stderr_tail: &[String],
+    outcome: ChildOutcome,
+) {
+    let success = exit_code == 0;
     let duration_str = format_duration(duration).to_string();
+    let status_str = match outcome {
+        ChildOutcome::Completed if success => "success".to_string(),
+        ChildOutcome::Completed => "failed".to_string(),
+        ChildOutcome::TimedOut => "timed_out".to_string(),
+        ChildOutcome::Signaled(signal) if signal > 0 => {
+            format!("signaled ({})", signal_name(signal))
+        }
+        ChildOutcome::Signaled(_) => "signaled".to_string(),
+    };
+    let command_str = conf.command.join(" ");
+    let output_tail_str = stderr_tail.join("\n");
+
+    let title_template = if success {
+        conf.title.as_str()
+    } else {
+        conf.fail_title.as_str()
+    };
+
+    let title = render_template(
+        title_template,
+        exit_code,
+        &duration_str,
+        &command_str,
+        status_str.as_str(),
+        &output_tail_str,
+    );
+
+    let icon = if success {
+        conf.icon.as_str()
+    } else {
+        conf.fail_icon.as_str()
+    };
+
+    let urgency = if success {
+        conf.urgency
+    } else {
+        conf.fail_urgency
+    };
 
-    let mut message = String::from(conf.message.as_str());
-    message.push('\n');
-    message.push_str(&format!("Completed in {}", duration_str));
+    let message = build_message(
+        conf.message.as_str(),
+        exit_code,
+        &duration_str,
+        &command_str,
+        status_str.as_str(),
+        &output_tail_str,
+        success,
+        stderr_tail,
+    );
 
     let mut notification = Notification::new();
-    notification.summary(conf.title.as_str());
+    notification.summary(title.as_str());
     notification.body(message.as_str());
     notification.timeout(conf.timeout);
     notification.appname("notify-complete");
 
+    if !icon.is_empty() {
+        notification.icon(icon);
+    }
+
     #[cfg(all(unix, not(target_os = "macos")))]
-    notification.urgency(conf.urgency);
+    notification.urgency(urgency);
 
     let result = notification.show();
 
@@ -30,37 +202,488 @@ fn send_notification(conf: &config::Config, _exit_code: i32, duration: Duration)
     }
 }
 
-fn spawn_child(conf: &Config) -> (i32, Duration) {
-    let start = Instant::now();
+/// Copies `reader` to `passthrough` line by line so the child's output
+/// still reaches the terminal, keeping only the last `tail_lines` lines
+/// (0 disables tail tracking) for the caller to inspect afterwards.
+fn tee_stream<R, W>(reader: R, mut passthrough: W, tail_lines: usize) -> JoinHandle<Vec<String>>
+where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut tail: VecDeque<String> = VecDeque::with_capacity(tail_lines);
 
-    let mut child = Command::new(conf.command[0].as_str())
-        .args(&conf.command[1..])
-        .spawn()
-        .expect("notify-complete: Error creating child process");
+        for line in BufReader::new(reader).lines().flatten() {
+            let _ = writeln!(passthrough, "{}", line);
+
+            if tail_lines > 0 {
+                if tail.len() == tail_lines {
+                    tail.pop_front();
+                }
+                tail.push_back(line);
+            }
+        }
+
+        tail.into_iter().collect()
+    })
+}
+
+/// Returns the conventional name of a Unix signal number (e.g. "SIGSEGV"),
+/// falling back to the raw number if it isn't recognized.
+#[cfg(unix)]
+fn signal_name(signum: i32) -> String {
+    use std::convert::TryFrom;
+
+    match nix::sys::signal::Signal::try_from(signum) {
+        Ok(signal) => format!("{:?}", signal),
+        Err(_) => format!("signal {}", signum),
+    }
+}
+
+#[cfg(windows)]
+fn signal_name(signum: i32) -> String {
+    format!("signal {}", signum)
+}
+
+/// Puts `command`'s child in its own process group (Unix) or process-group
+/// console (Windows) so it can be signaled as a unit, separately from
+/// notify-complete itself.
+#[cfg(unix)]
+fn configure_process_group(command: &mut process::Command, enabled: bool) {
+    use std::os::unix::process::CommandExt;
+
+    if enabled {
+        command.process_group(0);
+    }
+}
+
+#[cfg(windows)]
+fn configure_process_group(command: &mut process::Command, enabled: bool) {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+
+    if enabled {
+        command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+}
+
+/// Spawns a background thread that forwards SIGINT/SIGTERM/SIGHUP received by
+/// notify-complete to the child's process group, so a wrapped command in its
+/// own group still sees the signals a terminal would otherwise only deliver
+/// to the wrapper.
+#[cfg(unix)]
+fn install_signal_forwarding(pgid: Option<u32>) -> Option<JoinHandle<()>> {
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::Pid;
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+    use signal_hook::iterator::Signals;
+    use std::convert::TryFrom;
+
+    let pgid = pgid? as i32;
+    let mut signals = Signals::new([SIGINT, SIGTERM, SIGHUP]).ok()?;
+
+    Some(thread::spawn(move || {
+        for signum in signals.forever() {
+            if let Ok(signal) = Signal::try_from(signum) {
+                let _ = kill(Pid::from_raw(-pgid), signal);
+            }
+        }
+    }))
+}
+
+#[cfg(windows)]
+fn install_signal_forwarding(_pgid: Option<u32>) -> Option<JoinHandle<()>> {
+    None
+}
+
+/// Sends SIGTERM to `child`, giving it `KILL_GRACE_PERIOD` to exit before
+/// escalating to SIGKILL. The child is always left in a waitable state.
+///
+/// When `use_process_group` is set (i.e. `configure_process_group` put the
+/// child in its own group), both signals target the whole group via the
+/// negated pid, not just the group leader -- otherwise a shell running a
+/// pipeline (`--shell sh` with `cmd1 | cmd2`) would have only the shell
+/// killed, orphaning its still-running children.
+#[cfg(unix)]
+fn terminate_child(child: &mut Child, use_process_group: bool) {
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::Pid;
+
+    let pid = Pid::from_raw(signal_target(child, use_process_group));
+    let _ = kill(pid, Signal::SIGTERM);
+
+    let deadline = Instant::now() + KILL_GRACE_PERIOD;
+    while Instant::now() < deadline {
+        if matches!(child.try_wait(), Ok(Some(_))) {
+            return;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    // still alive after the grace period -- escalate to SIGKILL
+    if use_process_group {
+        let pid = Pid::from_raw(signal_target(child, use_process_group));
+        let _ = kill(pid, Signal::SIGKILL);
+    } else {
+        let _ = child.kill();
+    }
+}
+
+#[cfg(unix)]
+fn signal_target(child: &Child, use_process_group: bool) -> i32 {
+    let pid = child.id() as i32;
+    if use_process_group {
+        -pid
+    } else {
+        pid
+    }
+}
+
+#[cfg(windows)]
+fn terminate_child(child: &mut Child, _use_process_group: bool) {
+    let _ = child.kill();
+}
+
+#[cfg(unix)]
+fn exit_code_for_signal(signal: i32) -> i32 {
+    128 + signal
+}
+
+#[cfg(windows)]
+fn exit_code_for_signal(_signal: i32) -> i32 {
+    1
+}
+
+/// Waits for `child` to exit, killing it once `run_timeout` elapses.
+/// Returns `(exit_code, outcome)`; the child is always reaped before
+/// returning so no zombies are left behind. `use_process_group` must match
+/// what `configure_process_group` did for this child, so a timeout kill
+/// reaches a whole pipeline rather than just its group leader.
+fn wait_for_child(
+    child: &mut Child,
+    run_timeout: Option<Duration>,
+    use_process_group: bool,
+) -> (i32, ChildOutcome) {
+    let timed_out = match run_timeout {
+        None => false,
+        Some(timeout) => {
+            let deadline = Instant::now() + timeout;
+            loop {
+                match child
+                    .try_wait()
+                    .expect("notify-complete: Error polling child process")
+                {
+                    Some(_) => break false,
+                    None if Instant::now() >= deadline => {
+                        terminate_child(child, use_process_group);
+                        break true;
+                    }
+                    None => thread::sleep(Duration::from_millis(100)),
+                }
+            }
+        }
+    };
 
     let child_result = child
         .wait()
         .expect("notify-complete: Error waiting on child process");
 
-    // using as_secs here to reduce the precision
-    let elapsed_sec = Duration::from_secs((Instant::now() - start).as_secs());
+    if timed_out {
+        return (TIMED_OUT_EXIT_CODE, ChildOutcome::TimedOut);
+    }
 
-    let exit_code = match child_result.code() {
-        Some(code) => code,
+    match child_result.code() {
+        Some(code) => (code, ChildOutcome::Completed),
         None => {
-            eprintln!("notify-complete: Child killed by signal");
-            // since the child was killed and didn't exit normally, exit with an error
-            1
+            let signal = child_signal(&child_result);
+            (exit_code_for_signal(signal), ChildOutcome::Signaled(signal))
         }
+    }
+}
+
+#[cfg(unix)]
+fn child_signal(status: &std::process::ExitStatus) -> i32 {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal().unwrap_or(0)
+}
+
+#[cfg(windows)]
+fn child_signal(_status: &std::process::ExitStatus) -> i32 {
+    0
+}
+
+fn spawn_child(conf: &Config) -> (i32, Duration, Vec<String>, ChildOutcome) {
+    let start = Instant::now();
+
+    let mut command = conf.shell.to_command(&conf.command);
+    configure_process_group(&mut command, !conf.no_process_group);
+
+    if conf.capture {
+        command.stdout(Stdio::piped());
+        command.stderr(Stdio::piped());
+    }
+
+    let mut child = command
+        .spawn()
+        .expect("notify-complete: Error creating child process");
+
+    let pgid = if conf.no_process_group {
+        None
+    } else {
+        Some(child.id())
+    };
+    let _signal_forwarder = install_signal_forwarding(pgid);
+
+    let (stdout_handle, stderr_handle) = if conf.capture {
+        let stdout = child
+            .stdout
+            .take()
+            .expect("notify-complete: Error capturing child stdout");
+        let stderr = child
+            .stderr
+            .take()
+            .expect("notify-complete: Error capturing child stderr");
+
+        (
+            Some(tee_stream(stdout, io::stdout(), 0)),
+            Some(tee_stream(stderr, io::stderr(), conf.capture_lines)),
+        )
+    } else {
+        (None, None)
     };
 
-    (exit_code, elapsed_sec)
+    let (exit_code, outcome) =
+        wait_for_child(&mut child, conf.run_timeout, !conf.no_process_group);
+
+    let stderr_tail = match stderr_handle {
+        Some(handle) => handle.join().unwrap_or_default(),
+        None => Vec::new(),
+    };
+
+    if let Some(handle) = stdout_handle {
+        let _ = handle.join();
+    }
+
+    // using as_secs here to reduce the precision
+    let elapsed_sec = Duration::from_secs((Instant::now() - start).as_secs());
+
+    (exit_code, elapsed_sec, stderr_tail, outcome)
+}
+
+#[cfg(test)]
+mod render_template_tests {
+    use super::render_template;
+
+    #[test]
+    fn substitutes_known_tokens() {
+        let result = render_template(
+            "{command} {status} ({duration}, exit {exit_code})",
+            1,
+            "2s",
+            "echo hi",
+            "failed",
+            "",
+        );
+
+        assert_eq!(result, "echo hi failed (2s, exit 1)");
+    }
+
+    #[test]
+    fn leaves_unknown_tokens_verbatim() {
+        let result = render_template("{nope}", 0, "1s", "cmd", "success", "");
+
+        assert_eq!(result, "{nope}");
+    }
+
+    #[test]
+    fn escapes_literal_braces() {
+        let result = render_template("{{not a token}}", 0, "1s", "cmd", "success", "");
+
+        assert_eq!(result, "{not a token}");
+    }
+
+    #[test]
+    fn leaves_unterminated_token_verbatim() {
+        let result = render_template("prefix {exit_code", 0, "1s", "cmd", "success", "");
+
+        assert_eq!(result, "prefix {exit_code");
+    }
+
+    #[test]
+    fn includes_output_tail_when_requested() {
+        let result = render_template("{output_tail}", 1, "1s", "cmd", "failed", "line1\nline2");
+
+        assert_eq!(result, "line1\nline2");
+    }
+}
+
+#[cfg(test)]
+mod build_message_tests {
+    use super::build_message;
+
+    #[test]
+    fn appends_tail_on_failure_when_template_omits_it() {
+        let tail = vec!["line1".to_string(), "line2".to_string()];
+        let result = build_message(
+            "{status} in {duration}",
+            1,
+            "2s",
+            "cmd",
+            "failed",
+            "line1\nline2",
+            false,
+            &tail,
+        );
+
+        assert_eq!(result, "failed in 2s\n\nline1\nline2");
+    }
+
+    #[test]
+    fn does_not_duplicate_tail_when_template_already_references_it() {
+        let tail = vec!["line1".to_string(), "line2".to_string()];
+        let result = build_message(
+            "{status}: {output_tail}",
+            1,
+            "2s",
+            "cmd",
+            "failed",
+            "line1\nline2",
+            false,
+            &tail,
+        );
+
+        assert_eq!(result, "failed: line1\nline2");
+    }
+
+    #[test]
+    fn does_not_append_tail_on_success() {
+        let tail = vec!["line1".to_string()];
+        let result = build_message(
+            "{status} in {duration}",
+            0,
+            "2s",
+            "cmd",
+            "success",
+            "line1",
+            true,
+            &tail,
+        );
+
+        assert_eq!(result, "success in 2s");
+    }
+}
+
+#[cfg(test)]
+mod terminate_child_tests {
+    use super::{exit_code_for_signal, terminate_child};
+    use std::process::Command;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    #[cfg(unix)]
+    fn terminates_a_child_that_honors_sigterm() {
+        let mut child = Command::new("sleep").arg("5").spawn().unwrap();
+
+        // `sleep` doesn't trap SIGTERM, so this should succeed well before
+        // the SIGTERM -> SIGKILL grace period elapses.
+        let deadline = Instant::now() + Duration::from_secs(1);
+
+        terminate_child(&mut child, false);
+
+        while Instant::now() < deadline {
+            if matches!(child.try_wait(), Ok(Some(_))) {
+                return;
+            }
+        }
+
+        panic!("child was not terminated");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn terminates_the_whole_process_group_for_a_pipeline() {
+        use nix::sys::signal::kill;
+        use nix::unistd::Pid;
+        use std::os::unix::process::CommandExt;
+
+        let mut command = Command::new("sh");
+        command.arg("-c").arg("sleep 5 | cat");
+        command.process_group(0);
+        let mut child = command.spawn().unwrap();
+        let pgid = child.id() as i32;
+
+        terminate_child(&mut child, true);
+
+        // both the shell and `cat` -- not just the group leader -- must be
+        // gone, or the pipeline's worker processes would be orphaned.
+        let deadline = Instant::now() + Duration::from_secs(1);
+        loop {
+            if kill(Pid::from_raw(-pgid), None).is_err() {
+                return;
+            }
+            if Instant::now() >= deadline {
+                panic!("process group was not fully terminated");
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn exit_code_for_signal_adds_128() {
+        assert_eq!(exit_code_for_signal(9), 137);
+        assert_eq!(exit_code_for_signal(15), 143);
+    }
+}
+
+#[cfg(test)]
+mod wait_for_child_tests {
+    use super::{wait_for_child, ChildOutcome, TIMED_OUT_EXIT_CODE};
+    use std::process::Command;
+    use std::time::Duration;
+
+    #[test]
+    fn completes_normally_without_a_timeout() {
+        let mut child = Command::new("true").spawn().unwrap();
+        let (exit_code, outcome) = wait_for_child(&mut child, None, false);
+
+        assert_eq!(exit_code, 0);
+        assert_eq!(outcome, ChildOutcome::Completed);
+    }
+
+    #[test]
+    fn completes_normally_within_its_run_timeout() {
+        let mut child = Command::new("true").spawn().unwrap();
+        let (exit_code, outcome) =
+            wait_for_child(&mut child, Some(Duration::from_secs(5)), false);
+
+        assert_eq!(exit_code, 0);
+        assert_eq!(outcome, ChildOutcome::Completed);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn kills_child_that_exceeds_its_run_timeout() {
+        let mut child = Command::new("sleep").arg("5").spawn().unwrap();
+        let (exit_code, outcome) =
+            wait_for_child(&mut child, Some(Duration::from_millis(200)), false);
+
+        assert_eq!(exit_code, TIMED_OUT_EXIT_CODE);
+        assert_eq!(outcome, ChildOutcome::TimedOut);
+    }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let conf = Config::new();
-    let (exit_code, elapsed_sec) = spawn_child(&conf);
-    send_notification(&conf, exit_code, elapsed_sec);
+    let conf = match Config::new() {
+        Ok(conf) => conf,
+        Err(e) => {
+            eprintln!("notify-complete: {}", e);
+            process::exit(1);
+        }
+    };
+    let (exit_code, elapsed_sec, stderr_tail, outcome) = spawn_child(&conf);
+    send_notification(&conf, exit_code, elapsed_sec, &stderr_tail, outcome);
 
     match exit_code {
         0 => Ok(()),
@@ -0,0 +1,202 @@
+use std::fmt;
+use std::process::Command;
+
+/// Selects how the wrapped command is executed.
+///
+/// `None` execs `argv[0]` directly with the remaining entries as literal
+/// arguments, matching notify-complete's historical behavior. Every other
+/// variant joins the command into a single string and hands it to a shell,
+/// so operators like pipes, globs, and `&&` work the way they would on a
+/// terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shell {
+    None,
+    Sh,
+    Cmd,
+    Custom(String),
+}
+
+impl Shell {
+    pub fn parse(value: &str) -> Shell {
+        match value {
+            "none" => Shell::None,
+            "sh" => Shell::Sh,
+            "cmd" => Shell::Cmd,
+            other => Shell::Custom(other.to_string()),
+        }
+    }
+
+    pub fn default_shell() -> Shell {
+        Shell::None
+    }
+
+    /// Build the `Command` that will run `command` under this shell.
+    pub fn to_command(&self, command: &[String]) -> Command {
+        match self {
+            Shell::None => {
+                let mut cmd = Command::new(&command[0]);
+                cmd.args(&command[1..]);
+                cmd
+            }
+            Shell::Sh => {
+                let mut cmd = Command::new("sh");
+                cmd.arg("-c").arg(shell_join(command));
+                cmd
+            }
+            Shell::Cmd => {
+                let mut cmd = Command::new("cmd");
+                cmd.arg("/C").arg(cmd_join(command));
+                cmd
+            }
+            Shell::Custom(program) => {
+                let mut cmd = Command::new(program);
+                cmd.arg("-c").arg(shell_join(command));
+                cmd
+            }
+        }
+    }
+}
+
+/// Joins `command` into a single POSIX shell command line, single-quoting
+/// only the words that need it (embedded whitespace or quote characters,
+/// e.g. from a quoted TOML `command` string or a multi-word CLI argument)
+/// so they survive `sh -c` re-splitting them apart. Bare operator and glob
+/// tokens (`|`, `&&`, `*.txt`, ...) are left unquoted so pipelines and
+/// globs still work the way `--shell sh` promises.
+fn shell_join(command: &[String]) -> String {
+    command
+        .iter()
+        .map(|w| shell_quote_if_needed(w))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn shell_quote_if_needed(word: &str) -> String {
+    if word.is_empty() || word.chars().any(|c| c.is_whitespace() || c == '\'' || c == '"') {
+        shell_quote(word)
+    } else {
+        word.to_string()
+    }
+}
+
+/// Single-quotes `word` for a POSIX shell, escaping embedded `'` as `'\''`.
+fn shell_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', r#"'\''"#))
+}
+
+/// Joins `command` into a single `cmd.exe` command line, double-quoting any
+/// word containing whitespace or a `"` (with embedded quotes doubled).
+fn cmd_join(command: &[String]) -> String {
+    command.iter().map(|w| cmd_quote(w)).collect::<Vec<_>>().join(" ")
+}
+
+fn cmd_quote(word: &str) -> String {
+    if word.is_empty() || word.chars().any(|c| c.is_whitespace() || c == '"') {
+        format!("\"{}\"", word.replace('"', "\"\""))
+    } else {
+        word.to_string()
+    }
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Shell::None => write!(f, "none"),
+            Shell::Sh => write!(f, "sh"),
+            Shell::Cmd => write!(f, "cmd"),
+            Shell::Custom(program) => write!(f, "{}", program),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Shell;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn to_command_sh_quotes_words_with_whitespace() {
+        let command = vec![
+            "echo".to_string(),
+            "hello world".to_string(),
+            "foo".to_string(),
+        ];
+        let cmd = Shell::Sh.to_command(&command);
+
+        assert_eq!(cmd.get_program(), OsStr::new("sh"));
+        let args: Vec<&OsStr> = cmd.get_args().collect();
+        assert_eq!(
+            args,
+            vec![OsStr::new("-c"), OsStr::new("echo 'hello world' foo")]
+        );
+    }
+
+    #[test]
+    fn to_command_sh_escapes_embedded_single_quotes() {
+        let command = vec!["echo".to_string(), "it's here".to_string()];
+        let cmd = Shell::Sh.to_command(&command);
+
+        let args: Vec<&OsStr> = cmd.get_args().collect();
+        assert_eq!(
+            args,
+            vec![OsStr::new("-c"), OsStr::new(r#"echo 'it'\''s here'"#)]
+        );
+    }
+
+    #[test]
+    fn to_command_sh_passes_shell_operators_through_unquoted() {
+        let command = vec![
+            "grep".to_string(),
+            "foo".to_string(),
+            "|".to_string(),
+            "sort".to_string(),
+            "&&".to_string(),
+            "echo".to_string(),
+            "done".to_string(),
+        ];
+        let cmd = Shell::Sh.to_command(&command);
+
+        let args: Vec<&OsStr> = cmd.get_args().collect();
+        assert_eq!(
+            args,
+            vec![OsStr::new("-c"), OsStr::new("grep foo | sort && echo done")]
+        );
+    }
+
+    #[test]
+    fn to_command_cmd_quotes_words_with_whitespace() {
+        let command = vec![
+            "echo".to_string(),
+            "hello world".to_string(),
+            "foo".to_string(),
+        ];
+        let cmd = Shell::Cmd.to_command(&command);
+
+        assert_eq!(cmd.get_program(), OsStr::new("cmd"));
+        let args: Vec<&OsStr> = cmd.get_args().collect();
+        assert_eq!(
+            args,
+            vec![OsStr::new("/C"), OsStr::new("echo \"hello world\" foo")]
+        );
+    }
+
+    #[test]
+    fn parse_none() {
+        assert_eq!(Shell::parse("none"), Shell::None);
+    }
+
+    #[test]
+    fn parse_sh() {
+        assert_eq!(Shell::parse("sh"), Shell::Sh);
+    }
+
+    #[test]
+    fn parse_cmd() {
+        assert_eq!(Shell::parse("cmd"), Shell::Cmd);
+    }
+
+    #[test]
+    fn parse_custom_program() {
+        assert_eq!(Shell::parse("zsh"), Shell::Custom("zsh".to_string()));
+    }
+}